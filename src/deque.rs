@@ -0,0 +1,363 @@
+//! A lock-free, single-owner, multi-thief work-stealing deque, following
+//! the Chase-Lev algorithm (as used by crossbeam-deque and similar
+//! libraries). Each worker thread owns exactly one `Worker` end of a
+//! deque; `Stealer` handles may be cloned and shared freely with sibling
+//! threads that want to help out.
+//!
+//! The owner thread calls `push`/`pop` on its `bottom` index, which only
+//! it ever touches. Thieves race each other (and the owner, on the last
+//! element) via a CAS on the shared `top` index. The backing buffer
+//! doubles when the owner overflows it; the old buffer is retired into
+//! the owner's `retired` list rather than freed immediately, since a
+//! thief that already loaded the old buffer pointer may still be
+//! reading from it. Those retired buffers are only actually dropped
+//! when the `Worker` itself is dropped, by which point the pool has
+//! stopped handing out `Stealer`s into it.
+
+use job::JobRef;
+use std::cell::UnsafeCell;
+use std::sync::atomic::{AtomicIsize, AtomicPtr, Ordering};
+
+/// The minimum number of slots a freshly created deque's buffer holds.
+const MIN_CAPACITY: usize = 32;
+
+struct Buffer {
+    storage: Vec<UnsafeCell<Option<JobRef>>>,
+}
+
+// `Buffer` is shared between the owner and any number of thieves behind
+// an `AtomicPtr`; access to each slot is synchronized by the `top`/
+// `bottom` protocol in `Worker`/`Stealer`, not by the type system, so we
+// have to assert `Sync` by hand.
+unsafe impl Sync for Buffer {}
+
+impl Buffer {
+    fn new(capacity: usize) -> Self {
+        let mut storage = Vec::with_capacity(capacity);
+        for _ in 0..capacity {
+            storage.push(UnsafeCell::new(None));
+        }
+        Buffer { storage: storage }
+    }
+
+    fn capacity(&self) -> usize {
+        self.storage.len()
+    }
+
+    fn mask(&self) -> isize {
+        (self.capacity() - 1) as isize
+    }
+
+    unsafe fn put(&self, index: isize, job_ref: JobRef) {
+        let slot = &self.storage[(index & self.mask()) as usize];
+        *slot.get() = Some(job_ref);
+    }
+
+    unsafe fn get(&self, index: isize) -> JobRef {
+        let slot = &self.storage[(index & self.mask()) as usize];
+        (*slot.get()).expect("deque slot read before it was written")
+    }
+
+    unsafe fn grow(&self, bottom: isize, top: isize) -> Buffer {
+        let new_buffer = Buffer::new(self.capacity() * 2);
+        for i in top..bottom {
+            new_buffer.put(i, self.get(i));
+        }
+        new_buffer
+    }
+}
+
+/// The owning end of a deque. There is exactly one of these per worker
+/// thread; it is not `Sync` and must never be shared.
+pub struct Worker {
+    bottom: AtomicIsize,
+    top: AtomicIsize,
+
+    /// the current backing storage; thieves load this atomically so a
+    /// concurrent `grow` (which publishes a brand new `Buffer` here via
+    /// a `Release` store) can never hand out a half-written pointer
+    buffer: AtomicPtr<Buffer>,
+
+    /// retired buffers that an in-flight thief may still be reading
+    /// from; owner-only, kept alive until the `Worker` itself drops
+    retired: UnsafeCell<Vec<Box<Buffer>>>,
+}
+
+unsafe impl Send for Worker {}
+
+/// A cloneable handle that sibling threads use to steal from a
+/// `Worker`'s deque.
+#[derive(Clone)]
+pub struct Stealer {
+    bottom: *const AtomicIsize,
+    top: *const AtomicIsize,
+    buffer: *const AtomicPtr<Buffer>,
+}
+
+unsafe impl Send for Stealer {}
+unsafe impl Sync for Stealer {}
+
+impl PartialEq for Stealer {
+    /// Two `Stealer`s are equal if they point at the same `Worker`,
+    /// i.e. they were both obtained (possibly via separate `clone`s)
+    /// from the same `Worker::stealer()`. Used by `Registry::steal` to
+    /// recognize and skip a worker's own stealer handle.
+    fn eq(&self, other: &Stealer) -> bool {
+        self.bottom == other.bottom && self.top == other.top && self.buffer == other.buffer
+    }
+}
+
+/// Outcome of a `steal()` attempt.
+pub enum Steal {
+    /// the deque was empty
+    Empty,
+    /// another thief (or the owner) won the race for the element at
+    /// `top`; retry
+    Retry,
+    /// successfully stole a job
+    Data(JobRef),
+}
+
+impl Worker {
+    pub fn new() -> Self {
+        let buffer = Box::new(Buffer::new(MIN_CAPACITY));
+        Worker {
+            bottom: AtomicIsize::new(0),
+            top: AtomicIsize::new(0),
+            buffer: AtomicPtr::new(Box::into_raw(buffer)),
+            retired: UnsafeCell::new(Vec::new()),
+        }
+    }
+
+    /// Creates a `Stealer` handle that siblings can use to steal from
+    /// this worker's deque. May be called any number of times.
+    pub fn stealer(&self) -> Stealer {
+        Stealer {
+            bottom: &self.bottom,
+            top: &self.top,
+            buffer: &self.buffer,
+        }
+    }
+
+    /// Pushes a job onto the bottom of the deque. Only the owning
+    /// thread may call this.
+    pub fn push(&self, job_ref: JobRef) {
+        unsafe {
+            let bottom = self.bottom.load(Ordering::Relaxed);
+            let top = self.top.load(Ordering::Acquire);
+
+            let mut buffer = self.buffer.load(Ordering::Relaxed);
+            if bottom - top >= (*buffer).capacity() as isize - 1 {
+                let grown = Box::new((*buffer).grow(bottom, top));
+                let grown = Box::into_raw(grown);
+                // publish the new buffer before anyone reads past this
+                // point; thieves that already hold the old pointer keep
+                // working against it safely, since it is only retired
+                // (never freed) below
+                self.buffer.store(grown, Ordering::Release);
+                (*self.retired.get()).push(Box::from_raw(buffer));
+                buffer = grown;
+            }
+
+            (*buffer).put(bottom, job_ref);
+
+            // release: the write to `buffer` above must be visible
+            // before any thief can observe the new `bottom`
+            self.bottom.store(bottom + 1, Ordering::Release);
+        }
+    }
+
+    /// Pops a job off the bottom of the deque. Only the owning thread
+    /// may call this.
+    pub fn pop(&self) -> Option<JobRef> {
+        unsafe {
+            let bottom = self.bottom.load(Ordering::Relaxed) - 1;
+            let buffer = self.buffer.load(Ordering::Relaxed);
+            self.bottom.store(bottom, Ordering::SeqCst);
+
+            let top = self.top.load(Ordering::SeqCst);
+
+            if top > bottom {
+                // deque was already empty; restore `bottom`
+                self.bottom.store(bottom + 1, Ordering::Relaxed);
+                return None;
+            }
+
+            let job_ref = (*buffer).get(bottom);
+
+            if top == bottom {
+                // last element: race any thief for it via CAS on `top`
+                let won = self.top
+                    .compare_and_swap(top, top + 1, Ordering::SeqCst) == top;
+                self.bottom.store(bottom + 1, Ordering::Relaxed);
+                if !won {
+                    return None;
+                }
+            }
+
+            Some(job_ref)
+        }
+    }
+}
+
+impl Drop for Worker {
+    fn drop(&mut self) {
+        unsafe {
+            drop(Box::from_raw(self.buffer.load(Ordering::Relaxed)));
+        }
+    }
+}
+
+impl Stealer {
+    /// Attempts to steal one job from the top of the deque. Returns
+    /// `Steal::Retry` if it lost a race with another thief (or the
+    /// owner); callers should loop until they see `Empty` or `Data`.
+    pub fn steal(&self) -> Steal {
+        unsafe {
+            let top = (*self.top).load(Ordering::Acquire);
+            let bottom = (*self.bottom).load(Ordering::Acquire);
+
+            if top >= bottom {
+                return Steal::Empty;
+            }
+
+            // `Acquire` so that if we go on to win the CAS below, we are
+            // guaranteed to see a buffer at least as new as the one the
+            // owner published before writing this slot
+            let buffer = (*self.buffer).load(Ordering::Acquire);
+            let job_ref = (*buffer).get(top);
+
+            if (*self.top)
+                .compare_and_swap(top, top + 1, Ordering::SeqCst) == top {
+                Steal::Data(job_ref)
+            } else {
+                Steal::Retry
+            }
+        }
+    }
+
+    /// Convenience helper that retries on `Steal::Retry` until it gets
+    /// a definitive answer.
+    pub fn steal_loop(&self) -> Option<JobRef> {
+        loop {
+            match self.steal() {
+                Steal::Empty => return None,
+                Steal::Data(job_ref) => return Some(job_ref),
+                Steal::Retry => continue,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use job::{Job, JobMode};
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, AtomicUsize};
+    use std::thread;
+    use std::time::Duration;
+
+    struct CountingJob {
+        counter: Arc<AtomicUsize>,
+    }
+
+    impl Job for CountingJob {
+        unsafe fn execute(this: *const Self, mode: JobMode) {
+            let this = Box::from_raw(this as *mut Self);
+            if mode == JobMode::Execute {
+                this.counter.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+    }
+
+    fn counting_job(counter: &Arc<AtomicUsize>) -> JobRef {
+        let job = Box::new(CountingJob { counter: counter.clone() });
+        unsafe { JobRef::new(Box::into_raw(job)) }
+    }
+
+    #[test]
+    fn push_pop_runs_everything_once() {
+        let worker = Worker::new();
+        let counter = Arc::new(AtomicUsize::new(0));
+        const N: usize = 200;
+        for _ in 0..N {
+            worker.push(counting_job(&counter));
+        }
+
+        let mut popped = 0;
+        while let Some(job_ref) = worker.pop() {
+            unsafe { job_ref.execute(JobMode::Execute) };
+            popped += 1;
+        }
+
+        assert_eq!(popped, N);
+        assert_eq!(counter.load(Ordering::SeqCst), N);
+    }
+
+    #[test]
+    fn grow_preserves_every_pushed_job() {
+        let worker = Worker::new();
+        let counter = Arc::new(AtomicUsize::new(0));
+        // comfortably more than MIN_CAPACITY so this forces several
+        // `grow`s while jobs are still sitting in the deque
+        const N: usize = MIN_CAPACITY * 8 + 7;
+        for _ in 0..N {
+            worker.push(counting_job(&counter));
+        }
+
+        let mut popped = 0;
+        while let Some(job_ref) = worker.pop() {
+            unsafe { job_ref.execute(JobMode::Execute) };
+            popped += 1;
+        }
+
+        assert_eq!(popped, N);
+        assert_eq!(counter.load(Ordering::SeqCst), N);
+    }
+
+    #[test]
+    fn owner_and_thief_split_every_job_exactly_once() {
+        let worker = Worker::new();
+        let counter = Arc::new(AtomicUsize::new(0));
+        const N: usize = 20_000;
+        for _ in 0..N {
+            worker.push(counting_job(&counter));
+        }
+
+        let stealer = worker.stealer();
+        let owner_done = Arc::new(AtomicBool::new(false));
+        let thief_done = owner_done.clone();
+        let thief = thread::spawn(move || {
+            let mut stolen = 0;
+            loop {
+                match stealer.steal_loop() {
+                    Some(job_ref) => {
+                        unsafe { job_ref.execute(JobMode::Execute) };
+                        stolen += 1;
+                    }
+                    None => {
+                        if thief_done.load(Ordering::SeqCst) {
+                            break;
+                        }
+                        thread::sleep(Duration::from_micros(10));
+                    }
+                }
+            }
+            stolen
+        });
+
+        let mut popped = 0;
+        while let Some(job_ref) = worker.pop() {
+            unsafe { job_ref.execute(JobMode::Execute) };
+            popped += 1;
+        }
+        owner_done.store(true, Ordering::SeqCst);
+        let stolen = thief.join().unwrap();
+
+        // every job ran exactly once, split somehow between the owner
+        // popping and the thief stealing
+        assert_eq!(popped + stolen, N);
+        assert_eq!(counter.load(Ordering::SeqCst), N);
+    }
+}
@@ -0,0 +1,53 @@
+//! The basic unit of work that gets pushed onto a worker's deque and
+//! later executed by whichever thread (owner or thief) pops it.
+
+use std::mem;
+
+/// How a job should be treated once it is picked up for execution.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum JobMode {
+    /// run the job's body normally
+    Execute,
+    /// skip the body, but still perform whatever bookkeeping the job
+    /// would have done on completion (e.g. decrementing a scope's
+    /// counter); used to drain a cancelled scope quickly
+    Abort,
+}
+
+/// Implemented by the concrete job types (see `scope::HeapJob`).
+/// `execute` receives back the type-erased pointer that was handed to
+/// `JobRef::new`.
+pub trait Job {
+    unsafe fn execute(this: *const Self, mode: JobMode);
+}
+
+/// A type-erased handle to a job: a data pointer plus the `execute`
+/// function to call on it. This is what actually gets stored in a
+/// worker's deque, since the deque cannot be generic over every
+/// concrete job type that gets spawned into it.
+#[derive(Copy, Clone)]
+pub struct JobRef {
+    pointer: *const (),
+    execute_fn: unsafe fn(*const (), JobMode),
+}
+
+unsafe impl Send for JobRef {}
+
+impl JobRef {
+    /// Wraps a pointer to a concrete `Job` impl into a type-erased
+    /// `JobRef`. Unsafe because the caller must guarantee `this`
+    /// remains valid until the `JobRef` is executed (or aborted).
+    pub unsafe fn new<T>(this: *const T) -> JobRef
+        where T: Job
+    {
+        let fn_ptr: unsafe fn(*const T, JobMode) = <T as Job>::execute;
+        JobRef {
+            pointer: this as *const (),
+            execute_fn: mem::transmute(fn_ptr),
+        }
+    }
+
+    pub unsafe fn execute(&self, mode: JobMode) {
+        (self.execute_fn)(self.pointer, mode)
+    }
+}
@@ -0,0 +1,159 @@
+use super::*;
+use super::internal::*;
+use std::sync::{Arc, Mutex};
+use std::sync::mpsc::{sync_channel, Receiver};
+use std::thread;
+
+/// Default number of items batched into each `Vec<T>` chunk handed off
+/// to the pool.
+const DEFAULT_CHUNK_SIZE: usize = 128;
+
+/// Default number of chunks the feeder is allowed to get ahead of the
+/// consumer before it blocks (the `sync_channel` bound).
+const DEFAULT_CHANNEL_DEPTH: usize = 8;
+
+/// Default number of times an `EagerProducer` is willing to hand off a
+/// clone of itself to `split`, so at most one more than this many
+/// consumer tasks may end up pulling chunks off the channel concurrently. There is no
+/// length hint to split on (the source is a plain `Iterator`), so we
+/// just cap the fan-out at a fixed budget instead.
+const DEFAULT_SPLIT_BUDGET: usize = 31;
+
+/// Extension trait that lets any `Send`-able sequential `Iterator` be
+/// driven in parallel, by eagerly pulling ahead of the consumer on a
+/// dedicated feeder thread. Unlike `SliceIter` and friends, this does
+/// not require the source to support indexed splitting -- it works for
+/// one-shot sources like a file reader, a decoder, or a database
+/// cursor, at the cost of an extra thread and a bounded buffer of
+/// batched items.
+pub trait EagerIterator: Iterator {
+    /// Bridges `self` into a `ParallelIterator` using the default chunk
+    /// size and channel depth. See `par_eager_iter_with` to tune those.
+    fn par_eager_iter(self) -> EagerIter<Self::Item>
+        where Self: Sized + Send + 'static,
+              Self::Item: Send + 'static
+    {
+        self.par_eager_iter_with(DEFAULT_CHUNK_SIZE, DEFAULT_CHANNEL_DEPTH)
+    }
+
+    /// Like `par_eager_iter`, but lets the caller pick the batch size
+    /// (`chunk_size`) and how many batches the feeder may produce ahead
+    /// of the consumer (`channel_depth`), trading memory for how well
+    /// the feeder can hide the latency of a slow source.
+    fn par_eager_iter_with(self, chunk_size: usize, channel_depth: usize) -> EagerIter<Self::Item>
+        where Self: Sized + Send + 'static,
+              Self::Item: Send + 'static
+    {
+        let (sender, receiver) = sync_channel(channel_depth);
+
+        thread::spawn(move || {
+            let mut source = self;
+            loop {
+                let mut chunk = Vec::with_capacity(chunk_size);
+                for item in source.by_ref().take(chunk_size) {
+                    chunk.push(item);
+                }
+                if chunk.is_empty() {
+                    break;
+                }
+                let is_last = chunk.len() < chunk_size;
+                if sender.send(chunk).is_err() {
+                    // consumer hung up early (e.g. short-circuited); stop feeding
+                    break;
+                }
+                if is_last {
+                    break;
+                }
+            }
+        });
+
+        EagerIter { receiver: Arc::new(Mutex::new(receiver)) }
+    }
+}
+
+impl<I: Iterator> EagerIterator for I {}
+
+/// A `ParallelIterator` fed by chunks pulled ahead of time from a
+/// sequential source; see `EagerIterator::par_eager_iter`.
+pub struct EagerIter<T> {
+    receiver: Arc<Mutex<Receiver<Vec<T>>>>,
+}
+
+impl<T: Send> ParallelIterator for EagerIter<T> {
+    type Item = T;
+
+    fn drive_unindexed<C>(self, consumer: C) -> C::Result
+        where C: UnindexedConsumer<Self::Item>
+    {
+        let producer = EagerProducer {
+            receiver: self.receiver,
+            split_budget: DEFAULT_SPLIT_BUDGET,
+        };
+        bridge_unindexed(producer, consumer)
+    }
+}
+
+struct EagerProducer<T> {
+    receiver: Arc<Mutex<Receiver<Vec<T>>>>,
+    split_budget: usize,
+}
+
+impl<T: Send> UnindexedProducer for EagerProducer<T> {
+    type Item = T;
+
+    /// Hands a clone of the shared receiver to the other half, so both
+    /// halves race each other (and whatever they split off next) for
+    /// chunks off the same channel -- that's what actually gets the
+    /// `map`/reduce work for each chunk running on multiple pool
+    /// threads, rather than just pipelining the read ahead of a single
+    /// consumer. `split_budget` bounds how many times we're willing to
+    /// do this, since there's no length hint to split on otherwise.
+    fn split(self) -> (Self, Option<Self>) {
+        if self.split_budget == 0 {
+            return (self, None);
+        }
+
+        // This split uses up one unit of budget; divide what remains
+        // between the two halves rather than handing each the rest:
+        // otherwise each half keeps almost the full parent budget, and
+        // sustained splitting on either branch lets total fan-out
+        // multiply far past `split_budget`'s starting value instead of
+        // being bounded by it.
+        let remaining = self.split_budget - 1;
+        let other_budget = remaining / 2;
+        let this_budget = remaining - other_budget;
+
+        let other = EagerProducer {
+            receiver: self.receiver.clone(),
+            split_budget: other_budget,
+        };
+        let this = EagerProducer {
+            receiver: self.receiver,
+            split_budget: this_budget,
+        };
+        (this, Some(other))
+    }
+
+    fn fold_with<F>(self, mut folder: F) -> F
+        where F: Folder<Self::Item>
+    {
+        loop {
+            // only the chunk hand-off is serialized; the (potentially
+            // expensive) fold over its items happens outside the lock
+            let chunk = {
+                let receiver = self.receiver.lock().unwrap();
+                receiver.recv()
+            };
+            match chunk {
+                Ok(chunk) => {
+                    folder = folder.consume_iter(chunk);
+                    if folder.full() {
+                        break;
+                    }
+                }
+                Err(_) => break,
+            }
+        }
+        folder
+    }
+}
@@ -0,0 +1,99 @@
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+/// Restores input order on top of a scheduler that completes spawned
+/// work in arbitrary order. Callers tag each unit of work with a
+/// monotonically increasing sequence index before handing it off to the
+/// pool (e.g. via `Scope::spawn`), then feed the results back through
+/// `submit` in whatever order they happen to finish; `submit` hands
+/// back every result that is now safe to emit, in order.
+///
+/// This is meant for parallel pipelines -- decompress, transform, then
+/// write -- where the middle stage runs fully in parallel but the final
+/// write must see results in the same order the input was read.
+pub struct OrderedCollector<T> {
+    state: Mutex<OrderedState<T>>,
+}
+
+struct OrderedState<T> {
+    /// the next sequence index we are waiting to emit
+    next_expected: usize,
+
+    /// results that arrived before their turn, keyed by sequence index
+    pending: BTreeMap<usize, T>,
+}
+
+impl<T> OrderedCollector<T> {
+    pub fn new() -> Self {
+        OrderedCollector {
+            state: Mutex::new(OrderedState {
+                next_expected: 0,
+                pending: BTreeMap::new(),
+            }),
+        }
+    }
+
+    /// Submits the result for sequence index `index`. Returns every
+    /// result that is now ready to be emitted in order: just `value`
+    /// itself if it arrived in turn, `value` followed by any
+    /// contiguous successors that were already buffered if it unblocks
+    /// them, or nothing if `value` is still waiting on an earlier
+    /// index.
+    ///
+    /// A duplicate or stale `index` (one at or below what has already
+    /// been emitted, e.g. a retried submission) is dropped rather than
+    /// buffered, since `pending` would otherwise hold onto it forever
+    /// waiting for a `next_expected` that has already moved past it.
+    pub fn submit(&self, index: usize, value: T) -> Vec<T> {
+        let mut state = self.state.lock().unwrap();
+
+        if index < state.next_expected {
+            // already emitted; drop the duplicate instead of stashing
+            // it somewhere it can never drain
+            return Vec::new();
+        }
+
+        if index != state.next_expected {
+            state.pending.insert(index, value);
+            return Vec::new();
+        }
+
+        let mut ready = vec![value];
+        state.next_expected += 1;
+
+        while let Some(next) = state.pending.remove(&state.next_expected) {
+            ready.push(next);
+            state.next_expected += 1;
+        }
+
+        ready
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn submit_buffers_out_of_order_then_drains_in_order() {
+        let collector = OrderedCollector::new();
+
+        assert_eq!(collector.submit(2, "c"), Vec::<&str>::new());
+        assert_eq!(collector.submit(1, "b"), Vec::<&str>::new());
+        // index 0 unblocks the run of 0, 1, 2 that was waiting on it
+        assert_eq!(collector.submit(0, "a"), vec!["a", "b", "c"]);
+        // already emitted up through 2; the next in-order index drains alone
+        assert_eq!(collector.submit(3, "d"), vec!["d"]);
+    }
+
+    #[test]
+    fn submit_drops_duplicate_and_stale_indices() {
+        let collector = OrderedCollector::new();
+
+        assert_eq!(collector.submit(0, "a"), vec!["a"]);
+        // index 0 was already emitted; resubmitting it must not wedge
+        // into `pending` forever
+        assert_eq!(collector.submit(0, "a-again"), Vec::<&str>::new());
+        assert_eq!(collector.submit(1, "b"), vec!["b"]);
+    }
+}
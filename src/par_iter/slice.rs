@@ -2,6 +2,15 @@ use super::*;
 use super::internal::*;
 use std::iter::Rev;
 
+/// Like `ToParallelChunks`, but for slices cut into disjoint mutable
+/// chunks. See `par_chunks_mut`.
+pub trait ToParallelChunksMut<'data> {
+    type Item: Send + 'data;
+    type Iter: IndexedParallelIterator<Item = &'data mut [Self::Item]>;
+
+    fn par_chunks_mut(&'data mut self, chunk_size: usize) -> Self::Iter;
+}
+
 pub struct SliceIter<'data, T: 'data + Sync> {
     slice: &'data [T]
 }
@@ -33,6 +42,33 @@ impl<'data, T: Sync + 'data> ToParallelChunks<'data> for [T] {
     }
 }
 
+impl<'data, T: Send + 'data> IntoParallelIterator for &'data mut [T] {
+    type Item = &'data mut T;
+    type Iter = SliceIterMut<'data, T>;
+
+    fn into_par_iter(self) -> Self::Iter {
+        SliceIterMut { slice: self }
+    }
+}
+
+impl<'data, T: Send + 'data> IntoParallelIterator for &'data mut Vec<T> {
+    type Item = &'data mut T;
+    type Iter = SliceIterMut<'data, T>;
+
+    fn into_par_iter(self) -> Self::Iter {
+        SliceIterMut { slice: self }
+    }
+}
+
+impl<'data, T: Send + 'data> ToParallelChunksMut<'data> for [T] {
+    type Item = T;
+    type Iter = ChunksIterMut<'data, T>;
+
+    fn par_chunks_mut(&'data mut self, chunk_size: usize) -> Self::Iter {
+        ChunksIterMut { chunk_size: chunk_size, slice: self }
+    }
+}
+
 impl<'data, T: Sync + 'data> ParallelIterator for SliceIter<'data, T> {
     type Item = &'data T;
 
@@ -110,6 +146,87 @@ impl<'data, T: Sync + 'data> IndexedParallelIterator for ChunksIter<'data, T> {
     }
 }
 
+pub struct SliceIterMut<'data, T: 'data + Send> {
+    slice: &'data mut [T]
+}
+
+impl<'data, T: Send + 'data> ParallelIterator for SliceIterMut<'data, T> {
+    type Item = &'data mut T;
+
+    fn drive_unindexed<C>(self, consumer: C) -> C::Result
+        where C: UnindexedConsumer<Self::Item>
+    {
+        bridge(self, consumer)
+    }
+}
+
+impl<'data, T: Send + 'data> BoundedParallelIterator for SliceIterMut<'data, T> {
+    fn upper_bound(&mut self) -> usize {
+        ExactParallelIterator::len(self)
+    }
+
+    fn drive<C>(self, consumer: C) -> C::Result
+        where C: Consumer<Self::Item>
+    {
+        bridge(self, consumer)
+    }
+}
+
+impl<'data, T: Send + 'data> ExactParallelIterator for SliceIterMut<'data, T> {
+    fn len(&mut self) -> usize {
+        self.slice.len()
+    }
+}
+
+impl<'data, T: Send + 'data> IndexedParallelIterator for SliceIterMut<'data, T> {
+    fn with_producer<CB>(self, callback: CB) -> CB::Output
+        where CB: ProducerCallback<Self::Item>
+    {
+        callback.callback(SliceProducerMut { slice: self.slice })
+    }
+}
+
+pub struct ChunksIterMut<'data, T: 'data + Send> {
+    chunk_size: usize,
+    slice: &'data mut [T],
+}
+
+impl<'data, T: Send + 'data> ParallelIterator for ChunksIterMut<'data, T> {
+    type Item = &'data mut [T];
+
+    fn drive_unindexed<C>(self, consumer: C) -> C::Result
+        where C: UnindexedConsumer<Self::Item>
+    {
+        bridge(self, consumer)
+    }
+}
+
+impl<'data, T: Send + 'data> BoundedParallelIterator for ChunksIterMut<'data, T> {
+    fn upper_bound(&mut self) -> usize {
+        ExactParallelIterator::len(self)
+    }
+
+    fn drive<C>(self, consumer: C) -> C::Result
+        where C: Consumer<Self::Item>
+    {
+        bridge(self, consumer)
+    }
+}
+
+impl<'data, T: Send + 'data> ExactParallelIterator for ChunksIterMut<'data, T> {
+    fn len(&mut self) -> usize {
+        (self.slice.len() + (self.chunk_size - 1)) / self.chunk_size
+    }
+}
+
+impl<'data, T: Send + 'data> IndexedParallelIterator for ChunksIterMut<'data, T> {
+    fn with_producer<CB>(self, callback: CB) -> CB::Output
+        where CB: ProducerCallback<Self::Item>
+    {
+        callback.callback(SliceChunksProducerMut { chunk_size: self.chunk_size, slice: self.slice })
+    }
+}
+
 ///////////////////////////////////////////////////////////////////////////
 
 pub struct SliceProducer<'data, T: 'data + Sync> {
@@ -253,3 +370,149 @@ impl<'data, T: 'data + Sync> IntoIterator for SliceChunksRevProducer<'data, T> {
         self.slice.chunks(self.chunk_size).rev()
     }
 }
+
+///////////////////////////////////////////////////////////////////////////
+
+pub struct SliceProducerMut<'data, T: 'data + Send> {
+    slice: &'data mut [T]
+}
+
+pub struct SliceRevProducerMut<'data, T: 'data + Send> {
+    slice: &'data mut [T]
+}
+
+impl<'data, T: 'data + Send> Producer for SliceProducerMut<'data, T> {
+    type DoubleEndedIterator = ::std::slice::IterMut<'data, T>;
+    type RevProducer = SliceRevProducerMut<'data, T>;
+
+    fn cost(&mut self, len: usize) -> f64 {
+        len as f64
+    }
+
+    fn split_at(self, index: usize) -> (Self, Self) {
+        // Sound because `split_at_mut` hands out two disjoint, non-aliasing
+        // mutable slices, so each half can be sent to a different task.
+        let (left, right) = self.slice.split_at_mut(index);
+        (SliceProducerMut { slice: left }, SliceProducerMut { slice: right })
+    }
+
+    fn rev(self) -> Self::RevProducer {
+       SliceRevProducerMut {
+           slice: self.slice
+       }
+    }
+}
+
+impl<'data, T: 'data + Send> Producer for SliceRevProducerMut<'data, T> {
+    type DoubleEndedIterator = ::std::slice::IterMut<'data, T>;
+    type RevProducer = SliceProducerMut<'data, T>;
+
+    fn cost(&mut self, len: usize) -> f64 {
+        len as f64
+    }
+
+    fn split_at(self, index: usize) -> (Self, Self) {
+        //FIXME FIXME FIXME - this probably needs to be updated
+        let (left, right) = self.slice.split_at_mut(index);
+        (SliceRevProducerMut { slice: left }, SliceRevProducerMut { slice: right })
+    }
+
+    fn rev(self) -> Self::RevProducer {
+       SliceProducerMut {
+           slice: self.slice
+       }
+    }
+}
+
+impl<'data, T: 'data + Send> IntoIterator for SliceProducerMut<'data, T> {
+    type Item = &'data mut T;
+    type IntoIter = ::std::slice::IterMut<'data, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.slice.into_iter()
+    }
+}
+
+impl<'data, T: 'data + Send> IntoIterator for SliceRevProducerMut<'data, T> {
+    type Item = &'data mut T;
+    type IntoIter = Rev<::std::slice::IterMut<'data, T>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.slice.into_iter().rev()
+    }
+}
+
+pub struct SliceChunksProducerMut<'data, T: 'data + Send> {
+    chunk_size: usize,
+    slice: &'data mut [T]
+}
+
+pub struct SliceChunksRevProducerMut<'data, T: 'data + Send> {
+    chunk_size: usize,
+    slice: &'data mut [T]
+}
+
+impl<'data, T: 'data + Send> Producer for SliceChunksProducerMut<'data, T> {
+    type DoubleEndedIterator = ::std::slice::ChunksMut<'data, T>;
+    type RevProducer = SliceChunksRevProducerMut<'data, T>;
+
+    fn cost(&mut self, len: usize) -> f64 {
+        len as f64
+    }
+
+    fn split_at(self, index: usize) -> (Self, Self) {
+        let elem_index = index * self.chunk_size;
+        let (left, right) = self.slice.split_at_mut(elem_index);
+        (SliceChunksProducerMut { chunk_size: self.chunk_size, slice: left },
+         SliceChunksProducerMut { chunk_size: self.chunk_size, slice: right })
+    }
+
+    fn rev(self) -> Self::RevProducer {
+        SliceChunksRevProducerMut {
+            chunk_size: self.chunk_size,
+            slice: self.slice
+        }
+    }
+}
+
+impl<'data, T: 'data + Send> Producer for SliceChunksRevProducerMut<'data, T> {
+    type DoubleEndedIterator = ::std::slice::ChunksMut<'data, T>;
+    type RevProducer = SliceChunksProducerMut<'data, T>;
+
+    fn cost(&mut self, len: usize) -> f64 {
+        len as f64
+    }
+
+    fn split_at(self, index: usize) -> (Self, Self) {
+        //FIXME FIXME FIXME - this probably needs to be updated
+        let elem_index = index * self.chunk_size;
+        let (left, right) = self.slice.split_at_mut(elem_index);
+        (SliceChunksRevProducerMut { chunk_size: self.chunk_size, slice: left },
+         SliceChunksRevProducerMut { chunk_size: self.chunk_size, slice: right })
+    }
+
+    fn rev(self) -> Self::RevProducer {
+        SliceChunksProducerMut {
+            chunk_size: self.chunk_size,
+            slice: self.slice
+        }
+    }
+}
+
+impl<'data, T: 'data + Send> IntoIterator for SliceChunksProducerMut<'data, T> {
+    type Item = &'data mut [T];
+    type IntoIter = ::std::slice::ChunksMut<'data, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.slice.chunks_mut(self.chunk_size)
+    }
+}
+
+impl<'data, T: 'data + Send> IntoIterator for SliceChunksRevProducerMut<'data, T> {
+    type Item = &'data mut [T];
+    type IntoIter = Rev<::std::slice::ChunksMut<'data, T>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.slice.chunks_mut(self.chunk_size).rev()
+    }
+}
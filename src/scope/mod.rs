@@ -1,10 +1,10 @@
 use job::{Job, JobMode, JobRef};
 use std::any::Any;
-use std::cell::UnsafeCell;
+use std::cell::{Cell, UnsafeCell};
 use std::marker::PhantomData;
 use std::mem;
 use std::ptr;
-use std::sync::atomic::{AtomicUsize, AtomicPtr, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicUsize, AtomicPtr, Ordering};
 use std::sync::{Condvar, Mutex};
 use thread_pool::{self, WorkerThread};
 use unwind;
@@ -33,6 +33,18 @@ pub struct Scope<'scope> {
     /// used to block while waiting for jobs to complete
     job_completed_cvar: Condvar,
 
+    /// set by `cancel()`; once true, jobs that have not yet started
+    /// running are aborted instead of executed, so the scope can drain
+    /// quickly
+    cancelled: AtomicBool,
+
+    /// if created on a worker thread, that thread's `spawn_count` at
+    /// the moment the scope was created; used by
+    /// `block_till_jobs_complete` to know how far it may safely pop its
+    /// own deque without reaching into an enclosing scope's or `join`'s
+    /// jobs
+    local_spawn_start: Cell<usize>,
+
     marker: PhantomData<fn(&'scope ())>,
 }
 
@@ -212,12 +224,28 @@ pub struct Scope<'scope> {
 pub fn scope<'scope, OP, R>(op: OP) -> R
     where OP: for<'s> FnOnce(&'s Scope<'scope>) -> R
 {
+    // Snapshot how many jobs this thread has spawned so far, *before*
+    // `op` runs. Anything on top of the local deque above this count
+    // belongs to `op` (or its descendants) and is therefore fair game
+    // for this scope to pop while helping; anything at or below it
+    // belongs to an enclosing scope or `join` and must be left alone.
+    let local_spawn_start = unsafe {
+        let worker_thread = WorkerThread::current();
+        if worker_thread.is_null() {
+            0
+        } else {
+            (*worker_thread).spawn_count().get()
+        }
+    };
+
     let scope = Scope {
         counter: AtomicUsize::new(1),
         leak_counter: AtomicUsize::new(0),
         panic: AtomicPtr::new(ptr::null_mut()),
         mutex: Mutex::new(()),
         job_completed_cvar: Condvar::new(),
+        cancelled: AtomicBool::new(false),
+        local_spawn_start: Cell::new(local_spawn_start),
         marker: PhantomData,
     };
     if false { scope.fool_dead_code(); }
@@ -257,6 +285,38 @@ impl<'scope> Scope<'scope> {
         }
     }
 
+    /// Spawns a job into the fork-join scope `self`, like [`spawn`], but
+    /// skips the job entirely (never pushing it onto a deque) if the
+    /// scope has already been cancelled.
+    ///
+    /// [`spawn`]: #method.spawn
+    pub fn spawn_interruptible<BODY>(&self, body: BODY)
+        where BODY: FnOnce(&Scope<'scope>) + 'scope
+    {
+        if self.is_cancelled() {
+            return;
+        }
+        self.spawn(body)
+    }
+
+    /// Sets the cancellation flag for this scope. Jobs that have
+    /// already started running are unaffected, but any job that has
+    /// not yet begun executing will take the `JobMode::Abort` path
+    /// instead, so the scope drains quickly. Spawned closures can poll
+    /// [`is_cancelled`] to stop early as well.
+    ///
+    /// [`is_cancelled`]: #method.is_cancelled
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    /// True if [`cancel`] has been called on this scope.
+    ///
+    /// [`cancel`]: #method.cancel
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
     fn job_panicked(&self, err: Box<Any + Send + 'static>) {
         // capture the first error we see, free the rest
         let nil = ptr::null_mut();
@@ -293,9 +353,46 @@ impl<'scope> Scope<'scope> {
     }
 
     fn block_till_jobs_complete(&self) {
-        // wait for job counter to reach 0:
-        //
-        // FIXME -- if on a worker thread, we should be helping here
+        // If we are on a worker thread, don't just sit there parked while
+        // our own jobs (or siblings' jobs) are sitting in a deque
+        // somewhere -- help out by popping and executing work ourselves
+        // until the counter reaches zero or there is nothing left to
+        // steal. We only pop locally while `spawn_count` is still above
+        // `local_spawn_start`: once it comes back down to that
+        // snapshot, everything this scope pushed has been popped (by us
+        // or a thief), and anything still sitting under it on the deque
+        // belongs to an enclosing scope or `join`, not us -- popping
+        // that would violate its own LIFO discipline. From that point
+        // on we only steal through the registry, which is told to skip
+        // our own stealer (see `Registry::steal`) precisely so it can
+        // never reach back into that same deque from the top.
+        unsafe {
+            let worker_thread = WorkerThread::current();
+            if !worker_thread.is_null() {
+                let worker_thread = &*worker_thread;
+                let spawn_count = worker_thread.spawn_count();
+                let local_spawn_start = self.local_spawn_start.get();
+                while self.counter.load(Ordering::Acquire) > 0 {
+                    if spawn_count.get() > local_spawn_start {
+                        if let Some(job_ref) = worker_thread.pop() {
+                            spawn_count.set(spawn_count.get() - 1);
+                            job_ref.execute(JobMode::Execute);
+                            continue;
+                        }
+                    }
+                    if let Some(job_ref) = thread_pool::get_registry().steal(&worker_thread.stealer()) {
+                        job_ref.execute(JobMode::Execute);
+                    } else {
+                        break;
+                    }
+                }
+            }
+        }
+
+        // Either we are not on a worker thread, or we ran out of
+        // stealable work while some jobs are still outstanding (they are
+        // presumably executing elsewhere); fall back to parking on the
+        // condvar until the remaining jobs complete.
         let mut guard = self.mutex.lock().unwrap();
         while self.counter.load(Ordering::Acquire) > 0 {
             guard = self.job_completed_cvar.wait(guard).unwrap();
@@ -379,6 +476,13 @@ impl<'scope, BODY> Job for HeapJob<'scope, BODY>
 
         match mode {
             JobMode::Execute => {
+                if scope.is_cancelled() {
+                    // The scope was cancelled before this job got a
+                    // chance to run; re-dispatch through the `Abort`
+                    // arm below instead of running the body.
+                    return Self::execute(this as *const Self, JobMode::Abort);
+                }
+
                 let worker_thread = &*WorkerThread::current();
                 let start_count = worker_thread.spawn_count().get();
 
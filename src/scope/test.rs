@@ -0,0 +1,72 @@
+use super::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+#[test]
+fn cancelled_scope_drains_without_running_bodies() {
+    let ran = AtomicUsize::new(0);
+
+    scope(|s| {
+        // Cancel before anything is spawned, so every job below sees
+        // `is_cancelled()` true the moment it is picked up for
+        // execution, however long it sits queued first -- no race with
+        // a background worker grabbing one before `cancel` runs.
+        s.cancel();
+
+        for _ in 0..64 {
+            s.spawn(|_| {
+                ran.fetch_add(1, Ordering::SeqCst);
+            });
+        }
+    });
+
+    assert_eq!(ran.load(Ordering::SeqCst), 0);
+}
+
+#[test]
+fn deeply_nested_scopes_complete_without_hanging() {
+    // 20 outer jobs against a pool of POOL_SIZE (4) workers means every
+    // worker ends up running several outer jobs in turn, each of which
+    // opens and blocks on its own nested scope -- the scenario
+    // `block_till_jobs_complete`'s helping loop exists to keep from
+    // deadlocking a small pool.
+    const OUTER_JOBS: usize = 20;
+    const INNER_JOBS_PER_OUTER: usize = 5;
+
+    let outer_ran = AtomicUsize::new(0);
+    let inner_ran = AtomicUsize::new(0);
+
+    scope(|s| {
+        for _ in 0..OUTER_JOBS {
+            s.spawn(|_| {
+                outer_ran.fetch_add(1, Ordering::SeqCst);
+
+                scope(|inner| {
+                    for _ in 0..INNER_JOBS_PER_OUTER {
+                        inner.spawn(|_| {
+                            inner_ran.fetch_add(1, Ordering::SeqCst);
+                        });
+                    }
+                });
+            });
+        }
+    });
+
+    assert_eq!(outer_ran.load(Ordering::SeqCst), OUTER_JOBS);
+    assert_eq!(inner_ran.load(Ordering::SeqCst), OUTER_JOBS * INNER_JOBS_PER_OUTER);
+}
+
+#[test]
+fn spawn_interruptible_skips_pushing_once_cancelled() {
+    let ran = AtomicUsize::new(0);
+
+    scope(|s| {
+        s.cancel();
+        for _ in 0..8 {
+            s.spawn_interruptible(|_| {
+                ran.fetch_add(1, Ordering::SeqCst);
+            });
+        }
+    });
+
+    assert_eq!(ran.load(Ordering::SeqCst), 0);
+}
@@ -0,0 +1,177 @@
+//! Owns the per-thread state a pool worker needs to spawn and steal
+//! jobs, plus the shared `Registry` that lets workers (and non-worker
+//! threads calling into the pool) find work to steal.
+
+use deque;
+use job::{JobMode, JobRef};
+use std::cell::Cell;
+use std::collections::VecDeque;
+use std::sync::{Mutex, Once, ONCE_INIT};
+use std::thread;
+
+/// Number of background worker threads the lazily-started global pool
+/// runs. Fixed rather than sized off the machine, since nothing here
+/// depends on throughput -- just on there being more than one worker so
+/// spawned jobs actually get run (and stolen) by someone.
+const POOL_SIZE: usize = 4;
+
+thread_local! {
+    static WORKER_THREAD_STATE: Cell<*const WorkerThread> = Cell::new(0 as *const WorkerThread)
+}
+
+/// Per-thread state for a pool worker: its own end of a work-stealing
+/// deque (see `deque::Worker`), plus a running count of how many jobs
+/// this thread has spawned (used by `scope::HeapJob` to know how many
+/// of its own jobs are still sitting on top of the deque).
+pub struct WorkerThread {
+    deque: deque::Worker,
+    spawn_count: Cell<usize>,
+}
+
+impl WorkerThread {
+    pub fn new() -> WorkerThread {
+        WorkerThread {
+            deque: deque::Worker::new(),
+            spawn_count: Cell::new(0),
+        }
+    }
+
+    /// Returns a pointer to the calling thread's `WorkerThread`, or
+    /// null if the calling thread is not currently running as a pool
+    /// worker.
+    pub fn current() -> *const WorkerThread {
+        WORKER_THREAD_STATE.with(|t| t.get())
+    }
+
+    /// Installs `worker` as the calling thread's `WorkerThread` for the
+    /// duration of `worker`'s lifetime, and registers its `Stealer`
+    /// with `registry` so siblings can help drain it. Called once by
+    /// whatever spins up a pool thread.
+    pub fn enter(worker: &WorkerThread, registry: &Registry) {
+        registry.register(worker.deque.stealer());
+        WORKER_THREAD_STATE.with(|t| t.set(worker));
+    }
+
+    pub fn spawn_count(&self) -> &Cell<usize> {
+        &self.spawn_count
+    }
+
+    /// Returns a `Stealer` handle onto this thread's own deque, the
+    /// same one `enter` registered with the `Registry`. Used to tell
+    /// `Registry::steal` which entry to skip so a worker never reaches
+    /// into its own deque through the pool-wide steal path.
+    pub fn stealer(&self) -> deque::Stealer {
+        self.deque.stealer()
+    }
+
+    /// Pushes a job onto this thread's own deque. Only the owning
+    /// thread may call this.
+    pub fn push(&self, job_ref: JobRef) {
+        self.deque.push(job_ref);
+    }
+
+    /// Pops a job off this thread's own deque (LIFO). Only the owning
+    /// thread may call this.
+    pub fn pop(&self) -> Option<JobRef> {
+        self.deque.pop()
+    }
+}
+
+/// The shared registry: an injector queue for jobs spawned from outside
+/// the pool, plus one `Stealer` per live worker so any thread can help
+/// drain any other thread's deque once its own is empty.
+pub struct Registry {
+    injected: Mutex<VecDeque<JobRef>>,
+    stealers: Mutex<Vec<deque::Stealer>>,
+}
+
+impl Registry {
+    pub fn new() -> Registry {
+        Registry {
+            injected: Mutex::new(VecDeque::new()),
+            stealers: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Registers a freshly spun-up worker's stealer handle so other
+    /// threads can steal from it.
+    pub fn register(&self, stealer: deque::Stealer) {
+        self.stealers.lock().unwrap().push(stealer);
+    }
+
+    /// Injects jobs spawned from a non-worker thread; they sit in the
+    /// shared queue until some worker steals them.
+    pub fn inject(&self, job_refs: &[JobRef]) {
+        self.injected.lock().unwrap().extend(job_refs.iter().cloned());
+    }
+
+    /// Looks for one job to run: first from the shared injector queue,
+    /// then by trying each registered worker's deque in turn, skipping
+    /// `exclude` (the calling worker's own `Stealer`). Used both by
+    /// idle pool threads and by `Scope::block_till_jobs_complete` when
+    /// a worker is helping out while it waits on a scope.
+    ///
+    /// `exclude` matters: without it, a worker whose own jobs were
+    /// stolen out from under it (so its deque is empty save for an
+    /// enclosing scope's or `join`'s not-yet-run job sitting under
+    /// `top`) could steal that job from *itself* here, running it out
+    /// of turn through the top-steal path instead of waiting for it to
+    /// come up via its own `pop`.
+    pub fn steal(&self, exclude: &deque::Stealer) -> Option<JobRef> {
+        if let Some(job_ref) = self.injected.lock().unwrap().pop_front() {
+            return Some(job_ref);
+        }
+
+        let stealers = self.stealers.lock().unwrap();
+        for stealer in stealers.iter() {
+            if stealer == exclude {
+                continue;
+            }
+            if let Some(job_ref) = stealer.steal_loop() {
+                return Some(job_ref);
+            }
+        }
+        None
+    }
+}
+
+static mut GLOBAL_REGISTRY: *const Registry = 0 as *const Registry;
+static GLOBAL_REGISTRY_INIT: Once = ONCE_INIT;
+
+/// Returns the process-wide registry, spinning up its background
+/// worker threads on first use.
+pub fn get_registry() -> &'static Registry {
+    unsafe {
+        GLOBAL_REGISTRY_INIT.call_once(|| {
+            let registry = Box::into_raw(Box::new(Registry::new()));
+            GLOBAL_REGISTRY = registry;
+            // `registry` itself is a raw pointer and so not `Send`;
+            // reborrow it as the `&'static Registry` this function
+            // already hands out elsewhere before moving it into the
+            // closure, rather than moving the pointer.
+            let registry: &'static Registry = &*registry;
+            for _ in 0..POOL_SIZE {
+                thread::spawn(move || worker_main(registry));
+            }
+        });
+        &*GLOBAL_REGISTRY
+    }
+}
+
+/// The body every background pool thread runs for its entire lifetime:
+/// install itself as the current `WorkerThread`, then repeatedly try
+/// its own deque before stealing from the registry (which checks the
+/// injector queue, then every sibling's deque in turn).
+fn worker_main(registry: &Registry) {
+    let worker = WorkerThread::new();
+    WorkerThread::enter(&worker, registry);
+    loop {
+        if let Some(job_ref) = worker.pop() {
+            unsafe { job_ref.execute(JobMode::Execute); }
+        } else if let Some(job_ref) = registry.steal(&worker.stealer()) {
+            unsafe { job_ref.execute(JobMode::Execute); }
+        } else {
+            thread::yield_now();
+        }
+    }
+}